@@ -0,0 +1,169 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Default number of high bits of a hashed `op_num` used to partition the id
+/// space into mask ranges, mirroring Solana's `CrdsFilter` sharding: each
+/// filter only answers for ids whose top `mask_bits` match `mask`, so the
+/// false-positive rate of any single filter stays bounded no matter how far
+/// behind the peer has fallen.
+pub const DEFAULT_MASK_BITS: u32 = 3;
+
+pub const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.001;
+
+/// `op_num`s are small sequential counters, so their high bits are always
+/// zero; masking the raw value would put every operation in mask 0 and leave
+/// every other mask's filter empty. Hash first so the bits used for masking
+/// are uniformly distributed regardless of how `op_num` is assigned.
+fn mask_of(op_num: u64, mask_bits: u32) -> u64 {
+    if mask_bits == 0 {
+        0
+    } else {
+        let mut hasher = DefaultHasher::new();
+        op_num.hash(&mut hasher);
+        hasher.finish() >> (u64::BITS - mask_bits)
+    }
+}
+
+/// A fixed-size k-hash Bloom filter, sized up front for a target
+/// false-positive rate given an expected item count.
+pub struct BloomFilter {
+    bits: Vec<bool>,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let num_bits = optimal_num_bits(expected_items.max(1), false_positive_rate);
+        let num_hashes = optimal_num_hashes(num_bits, expected_items.max(1));
+        Self {
+            bits: vec![false; num_bits],
+            num_hashes,
+        }
+    }
+
+    pub fn insert(&mut self, op_num: u64) {
+        for seed in 0..self.num_hashes {
+            let idx = self.index_for(op_num, seed);
+            self.bits[idx] = true;
+        }
+    }
+
+    pub fn contains(&self, op_num: u64) -> bool {
+        (0..self.num_hashes).all(|seed| self.bits[self.index_for(op_num, seed)])
+    }
+
+    fn index_for(&self, op_num: u64, seed: u32) -> usize {
+        let mut hasher = DefaultHasher::new();
+        op_num.hash(&mut hasher);
+        seed.hash(&mut hasher);
+        (hasher.finish() as usize) % self.bits.len()
+    }
+}
+
+fn optimal_num_bits(expected_items: usize, false_positive_rate: f64) -> usize {
+    let n = expected_items as f64;
+    let m = -(n * false_positive_rate.ln()) / std::f64::consts::LN_2.powi(2);
+    (m.ceil() as usize).max(8)
+}
+
+fn optimal_num_hashes(num_bits: usize, expected_items: usize) -> u32 {
+    let ratio = num_bits as f64 / expected_items as f64;
+    ((ratio * std::f64::consts::LN_2).round() as u32).max(1)
+}
+
+/// One mask-partitioned Bloom filter summarizing the `op_num`s a lagging
+/// peer has already applied, as sent in a pull request to a source peer.
+pub struct WalDeltaFilter {
+    mask_bits: u32,
+    mask: u64,
+    bloom: BloomFilter,
+}
+
+impl WalDeltaFilter {
+    fn matches_mask(&self, op_num: u64) -> bool {
+        mask_of(op_num, self.mask_bits) == self.mask
+    }
+}
+
+/// Summarize `applied_op_nums` into one filter per mask value, so each
+/// filter's false-positive rate stays bounded regardless of how large the
+/// backlog behind it is.
+pub fn build_delta_filters(
+    applied_op_nums: impl IntoIterator<Item = u64>,
+    mask_bits: u32,
+    false_positive_rate: f64,
+) -> Vec<WalDeltaFilter> {
+    let applied: Vec<u64> = applied_op_nums.into_iter().collect();
+    let num_masks = 1u64 << mask_bits;
+
+    (0..num_masks)
+        .map(|mask| {
+            let in_range: Vec<u64> = applied
+                .iter()
+                .copied()
+                .filter(|op_num| mask_of(*op_num, mask_bits) == mask)
+                .collect();
+            let mut bloom = BloomFilter::new(in_range.len(), false_positive_rate);
+            for op_num in &in_range {
+                bloom.insert(*op_num);
+            }
+            WalDeltaFilter {
+                mask_bits,
+                mask,
+                bloom,
+            }
+        })
+        .collect()
+}
+
+/// Source-side half of the handshake: given the filters a lagging peer sent,
+/// return the `op_num`s from `local_op_nums` that are *not* present in their
+/// matching filter, i.e. the operations that need to be streamed back.
+pub fn missing_op_nums(
+    local_op_nums: impl IntoIterator<Item = u64>,
+    filters: &[WalDeltaFilter],
+) -> Vec<u64> {
+    local_op_nums
+        .into_iter()
+        .filter(|op_num| {
+            filters
+                .iter()
+                .find(|filter| filter.matches_mask(*op_num))
+                .map_or(true, |filter| !filter.bloom.contains(*op_num))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_of_distributes_across_masks() {
+        let mask_bits = DEFAULT_MASK_BITS;
+        let num_masks = 1u64 << mask_bits;
+        let mut seen = std::collections::HashSet::new();
+        for op_num in 0..1000u64 {
+            seen.insert(mask_of(op_num, mask_bits));
+        }
+        // A sequential run of op_nums should spread across every mask, not
+        // collapse onto a single one the way masking the raw counter did.
+        assert_eq!(seen.len(), num_masks as usize);
+    }
+
+    #[test]
+    fn missing_op_nums_finds_gaps_in_delta_filters() {
+        let applied: Vec<u64> = (0..200).step_by(2).collect();
+        let filters = build_delta_filters(applied, DEFAULT_MASK_BITS, DEFAULT_FALSE_POSITIVE_RATE);
+
+        let local: Vec<u64> = (0..200).collect();
+        let missing = missing_op_nums(local, &filters);
+
+        // Only odd op_nums were never applied by the peer, so only they may
+        // show up as missing; no even op_num (which the peer did apply) can,
+        // since a Bloom filter has no false negatives. A false positive may
+        // still hide an odd op_num, so not every odd one is required here.
+        assert!(missing.iter().all(|op_num| op_num % 2 == 1));
+        assert!((0..200).step_by(2).all(|op_num| !missing.contains(&op_num)));
+    }
+}
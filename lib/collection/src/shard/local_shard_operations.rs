@@ -18,7 +18,11 @@ use crate::operations::types::{
     OptimizersStatus, PointRequest, Record, SearchRequestBatch, UpdateResult, UpdateStatus,
 };
 use crate::operations::CollectionUpdateOperations;
+use crate::shard::bloom_sync;
+use crate::shard::change_feed::{ChangeFeedPage, PointChange};
 use crate::shard::local_shard::LocalShard;
+use crate::shard::merkle::{self, ShardMerkleTree};
+use crate::shard::metrics;
 use crate::shard::ShardOperation;
 use crate::update_handler::{OperationData, UpdateSignal};
 
@@ -32,6 +36,9 @@ impl ShardOperation for LocalShard {
         operation: CollectionUpdateOperations,
         wait: bool,
     ) -> CollectionResult<UpdateResult> {
+        let _timer =
+            metrics::start_operation_timer(&self.collection_name(), &self.shard_id().to_string(), "update");
+
         let (callback_sender, callback_receiver) = if wait {
             let (tx, rx) = oneshot::channel();
             (Some(tx), Some(rx))
@@ -43,7 +50,31 @@ impl ShardOperation for LocalShard {
             let update_sender = self.update_sender.load();
             let channel_permit = update_sender.reserve().await?;
             let mut wal_lock = self.wal.lock();
-            let operation_id = wal_lock.write(&operation)?;
+            let operation_id = {
+                let _wal_timer =
+                    metrics::start_wal_write_timer(&self.collection_name(), &self.shard_id().to_string());
+                wal_lock.write(&operation)?
+            };
+
+            // Dirty only the touched leaves (and their O(log n) ancestors) of
+            // the anti-entropy tree, rather than rehashing the whole shard.
+            let affected_points: Vec<(ExtendedPointId, bool)> =
+                operation.iter_affected_points().collect();
+
+            let mut merkle_tree = self.merkle_tree.write();
+            for (point_id, deleted) in affected_points.iter().copied() {
+                if deleted {
+                    merkle_tree.delete(point_id, operation_id);
+                } else {
+                    merkle_tree.upsert(point_id, operation_id);
+                }
+            }
+            drop(merkle_tree);
+
+            self.change_feed
+                .write()
+                .record_batch(operation_id, affected_points);
+
             channel_permit.send(UpdateSignal::Operation(OperationData {
                 op_num: operation_id,
                 operation,
@@ -74,6 +105,12 @@ impl ShardOperation for LocalShard {
         with_vector: &WithVector,
         filter: Option<&Filter>,
     ) -> CollectionResult<Vec<Record>> {
+        let _timer = metrics::start_operation_timer(
+            &self.collection_name(),
+            &self.shard_id().to_string(),
+            "scroll_by",
+        );
+
         // ToDo: Make faster points selection with a set
         let segments = self.segments();
         let point_ids = segments
@@ -149,7 +186,7 @@ impl ShardOperation for LocalShard {
             Some(error) => OptimizersStatus::Error(error.to_string()),
         };
 
-        Ok(CollectionInfo {
+        let info = CollectionInfo {
             status,
             optimizer_status,
             vectors_count,
@@ -158,7 +195,9 @@ impl ShardOperation for LocalShard {
             segments_count,
             config: collection_config,
             payload_schema: schema,
-        })
+        };
+        metrics::observe_info(&self.collection_name(), &self.shard_id().to_string(), &info);
+        Ok(info)
     }
 
     async fn search(
@@ -166,6 +205,12 @@ impl ShardOperation for LocalShard {
         request: Arc<SearchRequestBatch>,
         search_runtime_handle: &Handle,
     ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        let _timer = metrics::start_operation_timer(
+            &self.collection_name(),
+            &self.shard_id().to_string(),
+            "search",
+        );
+
         let collection_params = self.config.read().await.params.clone();
         // check vector names existing
         for req in &request.searches {
@@ -202,6 +247,9 @@ impl ShardOperation for LocalShard {
     }
 
     async fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult> {
+        let _timer =
+            metrics::start_operation_timer(&self.collection_name(), &self.shard_id().to_string(), "count");
+
         let total_count = if request.exact {
             let all_points = self.read_filtered(request.filter.as_ref()).await?;
             all_points.len()
@@ -219,6 +267,222 @@ impl ShardOperation for LocalShard {
         with_payload: &WithPayload,
         with_vector: &WithVector,
     ) -> CollectionResult<Vec<Record>> {
+        let _timer = metrics::start_operation_timer(
+            &self.collection_name(),
+            &self.shard_id().to_string(),
+            "retrieve",
+        );
+
         SegmentsSearcher::retrieve(self.segments(), &request.ids, with_payload, with_vector).await
     }
+
+    /// Root hash of this shard's Merkle anti-entropy tree over its
+    /// `(point_id, version)` pairs. Exposed on `ShardOperation`, rather than
+    /// only on the local inherent impl, so a remote shard can participate in
+    /// reconciliation the same way a local one does.
+    fn merkle_root(&self) -> merkle::Hash {
+        self.merkle_tree.read().root()
+    }
+
+    /// Hash of an arbitrary subtree, `level` 0 being the leaves. A
+    /// reconciling peer compares roots first, then recurses only into the
+    /// subtrees whose hashes disagree, bounding exchanged data to roughly
+    /// `O(d * log n)` for `d` diverging points.
+    fn merkle_subtree_hash(&self, level: usize, index: usize) -> Option<merkle::Hash> {
+        self.merkle_tree.read().subtree_hash(level, index)
+    }
+
+    /// Point ids whose leaves diverge from `other`'s tree. The caller
+    /// re-fetches these via [`ShardOperation::retrieve`] and applies them
+    /// through [`ShardOperation::update`] as usual.
+    fn diverging_point_ids(&self, other: &ShardMerkleTree) -> Vec<ExtendedPointId> {
+        self.merkle_tree.read().diverging_point_ids(other)
+    }
+}
+
+impl LocalShard {
+    /// Summarize this shard's WAL into Bloom filters to send as a pull
+    /// request when rejoining after a partition, so a source peer can ship
+    /// back only the operations actually missing rather than a full state
+    /// transfer.
+    pub fn wal_delta_filters(&self) -> Vec<bloom_sync::WalDeltaFilter> {
+        let wal = self.wal.lock();
+        bloom_sync::build_delta_filters(
+            wal.first_index()..=wal.last_index(),
+            bloom_sync::DEFAULT_MASK_BITS,
+            bloom_sync::DEFAULT_FALSE_POSITIVE_RATE,
+        )
+    }
+
+    /// Source side of the delta-sync handshake: scan this shard's WAL and
+    /// return the operations missing from the lagging peer's `filters`.
+    pub fn wal_operations_missing_from(
+        &self,
+        filters: &[bloom_sync::WalDeltaFilter],
+    ) -> CollectionResult<Vec<(u64, CollectionUpdateOperations)>> {
+        let wal = self.wal.lock();
+        let missing = bloom_sync::missing_op_nums(wal.first_index()..=wal.last_index(), filters);
+        missing
+            .into_iter()
+            .map(|op_num| Ok((op_num, wal.read(op_num)?)))
+            .collect()
+    }
+
+    /// Replay operations streamed back from a source peer during delta-sync,
+    /// feeding them through the same `UpdateSignal::Operation` path used by
+    /// regular writes so they apply identically, without re-appending them
+    /// to this shard's own WAL (they already carry their source `op_num`).
+    ///
+    /// Maintains `merkle_tree` and `change_feed` the same way
+    /// `ShardOperation::update` does before sending the signal, so a
+    /// delta-synced shard's Merkle root matches the source peer's without a
+    /// further reconciliation round, and CDC consumers see delta-synced
+    /// points the same as any other write.
+    pub async fn replay_delta_sync(
+        &self,
+        operations: Vec<(u64, CollectionUpdateOperations)>,
+    ) -> CollectionResult<()> {
+        let update_sender = self.update_sender.load();
+        for (op_num, operation) in operations {
+            let channel_permit = update_sender.reserve().await?;
+
+            let affected_points: Vec<(ExtendedPointId, bool)> =
+                operation.iter_affected_points().collect();
+
+            let mut merkle_tree = self.merkle_tree.write();
+            for (point_id, deleted) in affected_points.iter().copied() {
+                if deleted {
+                    merkle_tree.delete(point_id, op_num);
+                } else {
+                    merkle_tree.upsert(point_id, op_num);
+                }
+            }
+            drop(merkle_tree);
+
+            self.change_feed.write().record_batch(op_num, affected_points);
+
+            channel_permit.send(UpdateSignal::Operation(OperationData {
+                op_num,
+                operation,
+                sender: None,
+            }));
+        }
+        Ok(())
+    }
+
+    /// Rebuild the in-memory change-feed index from the WAL, so a consumer's
+    /// cursor keeps resolving correctly across a shard restart instead of
+    /// silently resuming from an index that was wiped with the process.
+    /// Should be called once during shard initialization, before the shard
+    /// starts accepting new writes.
+    pub fn rebuild_change_feed(&self) {
+        let wal = self.wal.lock();
+        let mut change_feed = self.change_feed.write();
+        for op_num in wal.first_index()..=wal.last_index() {
+            let Ok(operation) = wal.read(op_num) else {
+                continue;
+            };
+            let affected_points: Vec<(ExtendedPointId, bool)> =
+                operation.iter_affected_points().collect();
+            change_feed.record_batch(op_num, affected_points);
+        }
+    }
+
+    /// Rebuild the in-memory Merkle anti-entropy tree from the WAL. Without
+    /// this, a restarted shard's tree starts empty while its segments still
+    /// hold real data, so `diverging_point_ids` would report the entire
+    /// dataset as diverging until a full re-reconciliation. Mirrors
+    /// `rebuild_change_feed`; should be called once during shard
+    /// initialization, before the shard starts accepting new writes.
+    pub fn rebuild_merkle_tree(&self) {
+        let wal = self.wal.lock();
+        let mut merkle_tree = self.merkle_tree.write();
+        for op_num in wal.first_index()..=wal.last_index() {
+            let Ok(operation) = wal.read(op_num) else {
+                continue;
+            };
+            for (point_id, deleted) in operation.iter_affected_points() {
+                if deleted {
+                    merkle_tree.delete(point_id, op_num);
+                } else {
+                    merkle_tree.upsert(point_id, op_num);
+                }
+            }
+        }
+    }
+
+    /// Stream points changed strictly after `cursor`, in ascending ordinal
+    /// order, along with the cursor to resume from. Unlike `scroll_by`, the
+    /// ordinal index is stable and resumable under concurrent writes, making
+    /// this suitable for CDC, cache invalidation, or seeding a new replica
+    /// before switching it over to live WAL streaming.
+    pub async fn changes_since(
+        &self,
+        cursor: Option<u64>,
+        limit: usize,
+        filter: Option<&Filter>,
+        with_payload: &WithPayload,
+        with_vector: &WithVector,
+    ) -> CollectionResult<ChangeFeedPage> {
+        let (changed, next_cursor) = self.change_feed.read().changes_since(cursor, limit);
+
+        // ToDo: this rescans every point in every segment on each page to
+        // evaluate the filter, rather than just the page's candidate ids;
+        // make faster once segments expose a direct "does id match" check.
+        let allowed_ids: Option<std::collections::HashSet<ExtendedPointId>> = filter.map(|filter| {
+            self.segments()
+                .read()
+                .iter()
+                .flat_map(|(_, segment)| segment.get().read().read_filtered(None, None, Some(filter)))
+                .collect()
+        });
+        // A deleted point is absent from every segment, so it can never pass
+        // `allowed_ids`; only gate live upserts on the filter and always let
+        // tombstones through, otherwise a filtered consumer (e.g. one
+        // maintaining a cache) would never observe a deletion and so never
+        // evict the entry it invalidates.
+        let is_allowed = |point_id: &ExtendedPointId, deleted: bool| {
+            deleted || allowed_ids.as_ref().map_or(true, |ids| ids.contains(point_id))
+        };
+
+        let filtered: Vec<(ExtendedPointId, bool)> = changed
+            .into_iter()
+            .filter(|(point_id, deleted)| is_allowed(point_id, *deleted))
+            .collect();
+
+        let live_ids: Vec<ExtendedPointId> = filtered
+            .iter()
+            .filter(|(_, deleted)| !deleted)
+            .map(|(point_id, _)| *point_id)
+            .collect();
+
+        let records =
+            SegmentsSearcher::retrieve(self.segments(), &live_ids, with_payload, with_vector).await?;
+        let mut records_by_id: HashMap<ExtendedPointId, Record> =
+            records.into_iter().map(|record| (record.id, record)).collect();
+
+        let changes = filtered
+            .into_iter()
+            .map(|(point_id, deleted)| {
+                // A live upsert's WAL write and its segment apply are not
+                // atomic, so `retrieve` can still miss a point the change
+                // feed already considers live; `record: None` there just
+                // means "not applied yet", not "deleted". Trust the change
+                // feed's own `deleted` flag instead of inferring it from a
+                // missing retrieve, which would permanently mislabel the
+                // point as deleted even once it lands.
+                let record = records_by_id.remove(&point_id);
+                PointChange {
+                    point_id,
+                    deleted,
+                    record,
+                }
+            })
+            .collect();
+
+        Ok(ChangeFeedPage {
+            changes,
+            next_cursor,
+        })
+    }
 }
@@ -0,0 +1,261 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash as StdHash, Hasher};
+
+use segment::types::ExtendedPointId;
+use sha3::{Digest, Sha3_256};
+
+pub type Hash = [u8; 32];
+
+const EMPTY: Hash = [0; 32];
+
+/// Fixed number of leaf buckets, same for every shard's tree regardless of
+/// how many points it holds. A point's bucket is `hash(point_id) %
+/// NUM_LEAVES`, not its position in a sorted list, so adding or removing
+/// *other* points never reassigns which bucket a given point falls into —
+/// the property `diverging_point_ids` below depends on to compare two
+/// differently-sized replicas leaf-for-leaf. Must be a power of two.
+const NUM_LEAVES: usize = 256;
+
+fn bucket_of(point_id: ExtendedPointId) -> usize {
+    let mut hasher = DefaultHasher::new();
+    point_id.hash(&mut hasher);
+    (hasher.finish() as usize) % NUM_LEAVES
+}
+
+fn point_hash(point_id: ExtendedPointId, version: u64, deleted: bool) -> Hash {
+    let mut hasher = Sha3_256::new();
+    hasher.update(point_id.to_string().as_bytes());
+    if deleted {
+        hasher.update(b"tombstone");
+    } else {
+        hasher.update(version.to_be_bytes());
+    }
+    hasher.finalize().into()
+}
+
+fn parent_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha3_256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn xor(a: Hash, b: Hash) -> Hash {
+    let mut out = [0u8; 32];
+    for i in 0..32 {
+        out[i] = a[i] ^ b[i];
+    }
+    out
+}
+
+/// One leaf bucket: the order-independent XOR of every member point's hash,
+/// plus the members themselves so a diverging bucket can be resolved down to
+/// exact point ids.
+#[derive(Default, Clone)]
+struct Bucket {
+    members: HashMap<ExtendedPointId, (u64, bool)>,
+    hash: Hash,
+}
+
+impl Bucket {
+    fn upsert(&mut self, point_id: ExtendedPointId, version: u64, deleted: bool) {
+        if let Some((old_version, old_deleted)) = self.members.get(&point_id).copied() {
+            self.hash = xor(self.hash, point_hash(point_id, old_version, old_deleted));
+        }
+        self.members.insert(point_id, (version, deleted));
+        self.hash = xor(self.hash, point_hash(point_id, version, deleted));
+    }
+}
+
+/// Merkle tree over a shard's `(point_id, version)` pairs, bucketed by
+/// `hash(point_id)` into a fixed number of leaves ([`NUM_LEAVES`]).
+///
+/// A deleted point keeps a tombstone entry (`H(point_id ++ "tombstone")`)
+/// rather than being removed from its bucket, so two replicas can still
+/// converge on a deletion instead of one of them re-fetching a point that no
+/// longer exists. Updating a point dirties only its bucket and the O(log
+/// `NUM_LEAVES`) path to the root. Because the tree shape and bucket
+/// assignment never depend on which points are actually present, two
+/// replicas with different point sets remain leaf-for-leaf comparable.
+pub struct ShardMerkleTree {
+    buckets: Vec<Bucket>,
+    levels: Vec<Vec<Hash>>,
+}
+
+impl Default for ShardMerkleTree {
+    fn default() -> Self {
+        let buckets = vec![Bucket::default(); NUM_LEAVES];
+        let levels = build_levels(vec![EMPTY; NUM_LEAVES]);
+        Self { buckets, levels }
+    }
+}
+
+impl ShardMerkleTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn build(points: impl IntoIterator<Item = (ExtendedPointId, u64, bool)>) -> Self {
+        let mut tree = Self::new();
+        for (point_id, version, deleted) in points {
+            tree.set_leaf(point_id, version, deleted);
+        }
+        tree
+    }
+
+    /// Upsert `point_id` at `version`, dirtying only its bucket and the
+    /// O(log `NUM_LEAVES`) path to the root.
+    pub fn upsert(&mut self, point_id: ExtendedPointId, version: u64) {
+        self.set_leaf(point_id, version, false);
+    }
+
+    /// Mark `point_id` as deleted at `version`, keeping a tombstone entry so
+    /// reconciliation still converges on the deletion.
+    pub fn delete(&mut self, point_id: ExtendedPointId, version: u64) {
+        self.set_leaf(point_id, version, true);
+    }
+
+    fn set_leaf(&mut self, point_id: ExtendedPointId, version: u64, deleted: bool) {
+        let bucket_idx = bucket_of(point_id);
+        self.buckets[bucket_idx].upsert(point_id, version, deleted);
+        self.levels[0][bucket_idx] = self.buckets[bucket_idx].hash;
+        self.propagate(bucket_idx);
+    }
+
+    /// Recompute ancestors of leaf `idx` up to the root.
+    fn propagate(&mut self, mut idx: usize) {
+        for level in 0..self.levels.len() - 1 {
+            let parent_idx = idx / 2;
+            let left = self.levels[level][parent_idx * 2];
+            let right = self.levels[level][parent_idx * 2 + 1];
+            self.levels[level + 1][parent_idx] = parent_hash(&left, &right);
+            idx = parent_idx;
+        }
+    }
+
+    /// Root hash of the tree.
+    pub fn root(&self) -> Hash {
+        self.levels
+            .last()
+            .and_then(|level| level.first().copied())
+            .unwrap_or(EMPTY)
+    }
+
+    /// Hash of the node at `(level, index)`, `level` 0 being the leaf
+    /// buckets. The tree shape is fixed ([`NUM_LEAVES`] buckets), so two
+    /// shards' trees always have the same height and `(level, index)` always
+    /// addresses the same bucket in both.
+    pub fn subtree_hash(&self, level: usize, index: usize) -> Option<Hash> {
+        self.levels.get(level)?.get(index).copied()
+    }
+
+    pub fn height(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Diff this tree against `other`, descending only into subtrees whose
+    /// hashes disagree, and returning the point ids that differ (including
+    /// ids present on only one side). Bounds comparison traffic to roughly
+    /// `O(d * log n)` where `d` is the number of diverging points, rather
+    /// than a full leaf-by-leaf scan.
+    pub fn diverging_point_ids(&self, other: &ShardMerkleTree) -> Vec<ExtendedPointId> {
+        if self.root() == other.root() {
+            return Vec::new();
+        }
+        let mut diverging = Vec::new();
+        self.collect_diverging(other, self.levels.len() - 1, 0, &mut diverging);
+        diverging
+    }
+
+    fn collect_diverging(
+        &self,
+        other: &ShardMerkleTree,
+        level: usize,
+        index: usize,
+        out: &mut Vec<ExtendedPointId>,
+    ) {
+        let ours = self.subtree_hash(level, index);
+        let theirs = other.subtree_hash(level, index);
+        if ours == theirs {
+            return;
+        }
+        if level == 0 {
+            let our_bucket = &self.buckets[index].members;
+            let their_bucket = &other.buckets[index].members;
+            for (point_id, ours) in our_bucket {
+                if their_bucket.get(point_id) != Some(ours) {
+                    out.push(*point_id);
+                }
+            }
+            for point_id in their_bucket.keys() {
+                if !our_bucket.contains_key(point_id) {
+                    out.push(*point_id);
+                }
+            }
+            return;
+        }
+        self.collect_diverging(other, level - 1, index * 2, out);
+        self.collect_diverging(other, level - 1, index * 2 + 1, out);
+    }
+}
+
+fn build_levels(leaves: Vec<Hash>) -> Vec<Vec<Hash>> {
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+        let prev = levels.last().unwrap();
+        let next = prev
+            .chunks(2)
+            .map(|pair| parent_hash(&pair[0], &pair[1]))
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_trees_do_not_diverge() {
+        let points = vec![(1, 10, false), (2, 20, false), (3, 30, false)];
+        let a = ShardMerkleTree::build(points.clone());
+        let b = ShardMerkleTree::build(points);
+
+        assert_eq!(a.root(), b.root());
+        assert!(a.diverging_point_ids(&b).is_empty());
+    }
+
+    #[test]
+    fn diverging_point_is_found_when_value_differs() {
+        let a = ShardMerkleTree::build(vec![(1, 10, false), (2, 20, false)]);
+        let b = ShardMerkleTree::build(vec![(1, 10, false), (2, 21, false)]);
+
+        let diverging = a.diverging_point_ids(&b);
+        assert_eq!(diverging, vec![2]);
+    }
+
+    #[test]
+    fn diverging_point_sets_of_different_sizes_are_found() {
+        // This is the case that matters most for reconciliation: two
+        // replicas whose point sets actually differ, not just a value.
+        let a = ShardMerkleTree::build(vec![(1, 10, false), (2, 20, false), (3, 30, false)]);
+        let b = ShardMerkleTree::build(vec![(1, 10, false), (2, 20, false)]);
+
+        let mut diverging = a.diverging_point_ids(&b);
+        diverging.sort_unstable();
+        assert_eq!(diverging, vec![3]);
+    }
+
+    #[test]
+    fn tombstone_survives_deletion_for_reconciliation() {
+        let mut tree = ShardMerkleTree::build(vec![(1, 10, false)]);
+        let before = tree.root();
+        tree.delete(1, 11);
+        assert_ne!(tree.root(), before);
+
+        let other = ShardMerkleTree::build(vec![(1, 10, false)]);
+        assert_eq!(tree.diverging_point_ids(&other), vec![1]);
+    }
+}
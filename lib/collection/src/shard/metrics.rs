@@ -0,0 +1,127 @@
+use lazy_static::lazy_static;
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec, HistogramVec,
+    IntCounterVec, IntGaugeVec,
+};
+
+use crate::operations::types::{CollectionInfo, CollectionStatus, OptimizersStatus};
+
+lazy_static! {
+    pub static ref POINTS_COUNT: IntGaugeVec = register_int_gauge_vec!(
+        "qdrant_collection_points_count",
+        "Number of points in a collection shard",
+        &["collection", "shard"]
+    )
+    .unwrap();
+    pub static ref VECTORS_COUNT: IntGaugeVec = register_int_gauge_vec!(
+        "qdrant_collection_vectors_count",
+        "Number of vectors in a collection shard",
+        &["collection", "shard"]
+    )
+    .unwrap();
+    pub static ref INDEXED_VECTORS_COUNT: IntGaugeVec = register_int_gauge_vec!(
+        "qdrant_collection_indexed_vectors_count",
+        "Number of indexed vectors in a collection shard",
+        &["collection", "shard"]
+    )
+    .unwrap();
+    pub static ref SEGMENTS_COUNT: IntGaugeVec = register_int_gauge_vec!(
+        "qdrant_collection_segments_count",
+        "Number of segments in a collection shard",
+        &["collection", "shard"]
+    )
+    .unwrap();
+
+    /// 0 = Green, 1 = Yellow, 2 = Red; mirrors [`CollectionStatus`].
+    pub static ref COLLECTION_STATUS: IntGaugeVec = register_int_gauge_vec!(
+        "qdrant_collection_status",
+        "Collection status (0=green, 1=yellow, 2=red)",
+        &["collection", "shard"]
+    )
+    .unwrap();
+
+    /// 0 = Ok, 1 = Error; mirrors [`OptimizersStatus`].
+    pub static ref OPTIMIZER_STATUS: IntGaugeVec = register_int_gauge_vec!(
+        "qdrant_collection_optimizer_status",
+        "Optimizer status (0=ok, 1=error)",
+        &["collection", "shard"]
+    )
+    .unwrap();
+
+    pub static ref SHARD_OPERATIONS_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "qdrant_shard_operations_total",
+        "Number of shard operations processed",
+        &["collection", "shard", "operation"]
+    )
+    .unwrap();
+
+    pub static ref SHARD_OPERATION_DURATION_SECONDS: HistogramVec = register_histogram_vec!(
+        "qdrant_shard_operation_duration_seconds",
+        "Latency of shard operations",
+        &["collection", "shard", "operation"]
+    )
+    .unwrap();
+
+    pub static ref WAL_WRITE_DURATION_SECONDS: HistogramVec = register_histogram_vec!(
+        "qdrant_shard_wal_write_duration_seconds",
+        "Latency of the WAL write step of an update operation",
+        &["collection", "shard"]
+    )
+    .unwrap();
+}
+
+/// Record the aggregates from [`ShardOperation::info`](crate::shard::ShardOperation::info)
+/// as gauges labeled by `collection`/`shard`.
+pub fn observe_info(collection: &str, shard: &str, info: &CollectionInfo) {
+    POINTS_COUNT
+        .with_label_values(&[collection, shard])
+        .set(info.points_count as i64);
+    VECTORS_COUNT
+        .with_label_values(&[collection, shard])
+        .set(info.vectors_count as i64);
+    INDEXED_VECTORS_COUNT
+        .with_label_values(&[collection, shard])
+        .set(info.indexed_vectors_count as i64);
+    SEGMENTS_COUNT
+        .with_label_values(&[collection, shard])
+        .set(info.segments_count as i64);
+
+    let status = match info.status {
+        CollectionStatus::Green => 0,
+        CollectionStatus::Yellow => 1,
+        CollectionStatus::Red => 2,
+    };
+    COLLECTION_STATUS
+        .with_label_values(&[collection, shard])
+        .set(status);
+
+    let optimizer_status = match &info.optimizer_status {
+        OptimizersStatus::Ok => 0,
+        OptimizersStatus::Error(_) => 1,
+    };
+    OPTIMIZER_STATUS
+        .with_label_values(&[collection, shard])
+        .set(optimizer_status);
+}
+
+/// Count `operation` as processed right away and start a latency timer for
+/// it; the latency is observed when the returned timer is dropped. Call at
+/// the top of a `ShardOperation` method.
+pub fn start_operation_timer(
+    collection: &str,
+    shard: &str,
+    operation: &str,
+) -> prometheus::HistogramTimer {
+    SHARD_OPERATIONS_TOTAL
+        .with_label_values(&[collection, shard, operation])
+        .inc();
+    SHARD_OPERATION_DURATION_SECONDS
+        .with_label_values(&[collection, shard, operation])
+        .start_timer()
+}
+
+pub fn start_wal_write_timer(collection: &str, shard: &str) -> prometheus::HistogramTimer {
+    WAL_WRITE_DURATION_SECONDS
+        .with_label_values(&[collection, shard])
+        .start_timer()
+}
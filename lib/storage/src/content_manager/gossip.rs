@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use rand::seq::SliceRandom;
+use serde::Serialize;
+
+use crate::content_manager::consensus_ops::ConsensusOperations;
+use crate::dispatcher::Dispatcher;
+
+/// Number of peers pulled from on each convergence round.
+const GOSSIP_FANOUT: usize = 3;
+
+pub type PeerId = u64;
+
+/// Default staleness threshold (in milliseconds) after which a peer whose
+/// newest known record is older than this is considered `Dead`.
+pub const DEFAULT_STALENESS_THRESHOLD_MS: u64 = 30_000;
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// A single versioned gossip record for a peer.
+///
+/// Modelled after the CRDS records used by Solana's `cluster_info`: each peer
+/// periodically re-publishes its own record with an incremented `version`,
+/// and any two records for the same peer are resolved last-writer-wins by
+/// comparing `version` (ties broken by `wallclock`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerRecord {
+    pub peer_id: PeerId,
+    pub wallclock: u64,
+    pub version: u64,
+}
+
+impl PeerRecord {
+    pub fn heartbeat(peer_id: PeerId, version: u64) -> Self {
+        Self {
+            peer_id,
+            wallclock: now_millis(),
+            version,
+        }
+    }
+
+    /// Whether `other` should replace this record under last-writer-wins.
+    fn superseded_by(&self, other: &PeerRecord) -> bool {
+        (other.version, other.wallclock) > (self.version, self.wallclock)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerHealth {
+    Alive,
+    Dead,
+}
+
+/// Gossip control plane: a CRDS-style map of `PeerId -> PeerRecord`.
+///
+/// Every node pushes its own heartbeat into the map with an incremented
+/// version and periodically pulls the map from a few random peers to
+/// converge, without requiring a central authority. Peers whose newest
+/// record is older than `staleness_threshold_ms` are reported as `Dead` by
+/// [`GossipState::health`] and [`GossipState::dead_peers`].
+pub struct GossipState {
+    records: RwLock<HashMap<PeerId, PeerRecord>>,
+    staleness_threshold_ms: u64,
+}
+
+impl GossipState {
+    pub fn new(staleness_threshold_ms: u64) -> Self {
+        Self {
+            records: RwLock::new(HashMap::new()),
+            staleness_threshold_ms,
+        }
+    }
+
+    /// Merge an incoming record from a push or pull response, keeping the
+    /// higher-versioned record for that peer (last-writer-wins).
+    pub fn merge(&self, incoming: PeerRecord) {
+        let mut records = self.records.write();
+        match records.get(&incoming.peer_id) {
+            Some(current) if !current.superseded_by(&incoming) => {}
+            _ => {
+                records.insert(incoming.peer_id, incoming);
+            }
+        }
+    }
+
+    /// Merge a batch of records pulled from a peer during convergence.
+    pub fn merge_all(&self, incoming: impl IntoIterator<Item = PeerRecord>) {
+        for record in incoming {
+            self.merge(record);
+        }
+    }
+
+    /// Record a local heartbeat for `peer_id`, bumping its version past
+    /// whatever is currently known for it.
+    pub fn heartbeat(&self, peer_id: PeerId) {
+        let mut records = self.records.write();
+        let version = records.get(&peer_id).map_or(0, |record| record.version + 1);
+        records.insert(peer_id, PeerRecord::heartbeat(peer_id, version));
+    }
+
+    /// Pick up to `count` random peers (other than `exclude`) to pull gossip
+    /// records from, used by the periodic convergence task.
+    pub fn random_peers(&self, count: usize, exclude: PeerId) -> Vec<PeerId> {
+        let mut peers: Vec<PeerId> = self
+            .records
+            .read()
+            .keys()
+            .copied()
+            .filter(|peer_id| *peer_id != exclude)
+            .collect();
+        peers.shuffle(&mut rand::thread_rng());
+        peers.truncate(count);
+        peers
+    }
+
+    /// Snapshot of all known records, used to answer a peer's pull request.
+    pub fn snapshot(&self) -> Vec<PeerRecord> {
+        self.records.read().values().copied().collect()
+    }
+
+    /// Health of a single peer, or `None` if it has never been heard from.
+    pub fn health(&self, peer_id: PeerId) -> Option<PeerHealth> {
+        let records = self.records.read();
+        let record = records.get(&peer_id)?;
+        Some(self.health_of(record))
+    }
+
+    /// Peers whose newest record is older than the staleness threshold.
+    pub fn dead_peers(&self) -> Vec<PeerId> {
+        self.records
+            .read()
+            .values()
+            .filter(|record| self.health_of(record) == PeerHealth::Dead)
+            .map(|record| record.peer_id)
+            .collect()
+    }
+
+    fn health_of(&self, record: &PeerRecord) -> PeerHealth {
+        if now_millis().saturating_sub(record.wallclock) > self.staleness_threshold_ms {
+            PeerHealth::Dead
+        } else {
+            PeerHealth::Alive
+        }
+    }
+}
+
+impl Default for GossipState {
+    fn default() -> Self {
+        Self::new(DEFAULT_STALENESS_THRESHOLD_MS)
+    }
+}
+
+lazy_static! {
+    /// Process-wide gossip table, shared by the periodic convergence task and
+    /// the `/cluster/peer/{id}/health` endpoint.
+    pub static ref GOSSIP: GossipState = GossipState::default();
+}
+
+/// Drive gossip convergence and failure detection for as long as the process
+/// runs: on each tick, publish a heartbeat for `local_peer_id`, pull records
+/// from a few random known peers to converge, and propose removing any peer
+/// that both looks `Dead` and holds no shards. Intended to be spawned once as
+/// a background task alongside the dispatcher.
+pub async fn run_gossip_loop(dispatcher: Arc<Dispatcher>, local_peer_id: PeerId, interval: Duration) {
+    loop {
+        tokio::time::sleep(interval).await;
+
+        GOSSIP.heartbeat(local_peer_id);
+
+        for peer_id in GOSSIP.random_peers(GOSSIP_FANOUT, local_peer_id) {
+            let incoming = dispatcher.fetch_gossip_snapshot(peer_id).await;
+            GOSSIP.merge_all(incoming);
+        }
+
+        let Some(consensus_state) = dispatcher.consensus_state() else {
+            continue;
+        };
+        for dead_peer_id in GOSSIP.dead_peers() {
+            if dead_peer_id == local_peer_id {
+                continue;
+            }
+            if dispatcher.peer_has_shards(dead_peer_id).await {
+                continue;
+            }
+            let _ = consensus_state
+                .propose_consensus_op_with_await(ConsensusOperations::RemovePeer(dead_peer_id), None)
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn higher_version_supersedes_lower_version() {
+        let old = PeerRecord { peer_id: 1, wallclock: 100, version: 1 };
+        let new = PeerRecord { peer_id: 1, wallclock: 50, version: 2 };
+        assert!(old.superseded_by(&new));
+        assert!(!new.superseded_by(&old));
+    }
+
+    #[test]
+    fn equal_version_breaks_tie_by_wallclock() {
+        let earlier = PeerRecord { peer_id: 1, wallclock: 100, version: 1 };
+        let later = PeerRecord { peer_id: 1, wallclock: 200, version: 1 };
+        assert!(earlier.superseded_by(&later));
+        assert!(!later.superseded_by(&earlier));
+    }
+
+    #[test]
+    fn merge_keeps_higher_version_regardless_of_arrival_order() {
+        let state = GossipState::new(DEFAULT_STALENESS_THRESHOLD_MS);
+        state.merge(PeerRecord { peer_id: 1, wallclock: 100, version: 5 });
+        state.merge(PeerRecord { peer_id: 1, wallclock: 200, version: 3 });
+
+        let snapshot = state.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].version, 5);
+    }
+
+    #[test]
+    fn stale_peer_is_reported_dead() {
+        let state = GossipState::new(0);
+        state.merge(PeerRecord { peer_id: 1, wallclock: 0, version: 1 });
+        assert_eq!(state.health(1), Some(PeerHealth::Dead));
+        assert_eq!(state.health(2), None);
+    }
+}
@@ -0,0 +1,58 @@
+use actix_web::rt::time::Instant;
+use actix_web::{post, web, Responder};
+use segment::types::{Filter, WithPayload, WithPayloadInterface, WithVector};
+use serde::Deserialize;
+use storage::dispatcher::Dispatcher;
+
+use crate::actix::helpers::process_response;
+
+const DEFAULT_CHANGES_LIMIT: usize = 100;
+
+#[derive(Deserialize)]
+struct ChangesRequest {
+    cursor: Option<u64>,
+    limit: Option<usize>,
+    filter: Option<Filter>,
+    with_payload: Option<bool>,
+    with_vector: Option<bool>,
+}
+
+/// Tail a collection's ordinal change feed: points created, updated or
+/// deleted strictly after `cursor`, in ascending ordinal order, plus the
+/// cursor to resume from on the next call. Takes a body rather than query
+/// parameters so that an optional `filter` can be passed through.
+#[post("/collections/{name}/points/changes")]
+async fn changes_since(
+    dispatcher: web::Data<Dispatcher>,
+    path: web::Path<String>,
+    request: web::Json<ChangesRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let collection_name = path.into_inner();
+    let request = request.into_inner();
+
+    let with_payload_interface = WithPayloadInterface::Bool(request.with_payload.unwrap_or(true));
+    let with_payload = WithPayload::from(&with_payload_interface);
+    let with_vector = WithVector::Bool(request.with_vector.unwrap_or(false));
+
+    let response = match dispatcher.get_collection(&collection_name).await {
+        Ok(collection) => {
+            collection
+                .changes_since(
+                    request.cursor,
+                    request.limit.unwrap_or(DEFAULT_CHANGES_LIMIT),
+                    request.filter.as_ref(),
+                    &with_payload,
+                    &with_vector,
+                )
+                .await
+        }
+        Err(err) => Err(err),
+    };
+    process_response(response, timing)
+}
+
+// Configure services
+pub fn config_changes_api(cfg: &mut web::ServiceConfig) {
+    cfg.service(changes_since);
+}
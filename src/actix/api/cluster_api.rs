@@ -2,6 +2,7 @@ use actix_web::rt::time::Instant;
 use actix_web::{delete, get, web, Responder};
 use storage::content_manager::consensus_ops::ConsensusOperations;
 use storage::content_manager::errors::StorageError;
+use storage::content_manager::gossip;
 use storage::dispatcher::Dispatcher;
 
 use crate::actix::helpers::process_response;
@@ -41,7 +42,23 @@ async fn remove_peer(dispatcher: web::Data<Dispatcher>, peer_id: web::Path<u64>)
     process_response(response, timing)
 }
 
+#[get("/cluster/peer/{peer_id}/health")]
+async fn peer_health(peer_id: web::Path<u64>) -> impl Responder {
+    let timing = Instant::now();
+    let peer_id = peer_id.into_inner();
+
+    let response = match gossip::GOSSIP.health(peer_id) {
+        Some(health) => Ok(health),
+        None => Err(StorageError::NotFound {
+            description: format!("Peer {peer_id} is not known to the cluster"),
+        }),
+    };
+    process_response(response, timing)
+}
+
 // Configure services
 pub fn config_cluster_api(cfg: &mut web::ServiceConfig) {
-    cfg.service(cluster_status).service(remove_peer);
+    cfg.service(cluster_status)
+        .service(remove_peer)
+        .service(peer_health);
 }
@@ -0,0 +1,31 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use prometheus::{Encoder, TextEncoder};
+
+/// Render the shared metrics registry in the Prometheus text exposition
+/// format at `/metrics`. The shard operation counters and latency
+/// histograms update on every operation. The per-shard gauges are set each
+/// time `info` is computed for that shard; a scrape does not refresh them,
+/// so they show the values from the most recent `info` call.
+///
+/// Gathers from Prometheus's own default registry rather than a registry
+/// held on `Dispatcher`, since that's what `shard::metrics`'s
+/// `register_*_vec!` macros register the shard gauges/counters into.
+#[get("/metrics")]
+async fn metrics() -> impl Responder {
+    let metric_families = prometheus::gather();
+
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    if let Err(err) = encoder.encode(&metric_families, &mut buffer) {
+        return HttpResponse::InternalServerError().body(err.to_string());
+    }
+
+    HttpResponse::Ok()
+        .content_type(encoder.format_type())
+        .body(buffer)
+}
+
+// Configure services
+pub fn config_metrics_api(cfg: &mut web::ServiceConfig) {
+    cfg.service(metrics);
+}
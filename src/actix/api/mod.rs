@@ -0,0 +1,12 @@
+pub mod changes_api;
+pub mod cluster_api;
+pub mod metrics_api;
+
+use actix_web::web;
+
+/// Mount every HTTP API module's routes onto the actix app.
+pub fn config_api(cfg: &mut web::ServiceConfig) {
+    cluster_api::config_cluster_api(cfg);
+    metrics_api::config_metrics_api(cfg);
+    changes_api::config_changes_api(cfg);
+}